@@ -0,0 +1,107 @@
+use std::error::Error;
+use std::fmt::Display;
+
+use crate::id::Flake;
+
+/// The number of bytes in the wire representation of a [Flake].
+pub const FLAKE_SIZE: usize = 16;
+
+/// The byte order used when turning a [Flake] into bytes or reading one back. The string and serde
+/// representations use big-endian while [Flake::bytes] historically used little-endian; making the
+/// order explicit here removes that silent mismatch for callers marshalling IDs over the wire.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endianness {
+    Little,
+    Big,
+}
+
+/// Writes [Flake] identifiers into caller-supplied byte buffers without allocating.
+pub struct Encoder {
+    endianness: Endianness,
+}
+
+impl Encoder {
+    /// Create an encoder that emits identifiers in the given byte order.
+    pub fn new(endianness: Endianness) -> Encoder {
+        Encoder { endianness }
+    }
+
+    /// Encode `flake` into the front of `buf`, returning the number of bytes written. Fails with
+    /// [CodecErr::BufferTooSmall] if `buf` cannot hold a full identifier.
+    pub fn encode(&self, flake: &Flake, buf: &mut [u8]) -> Result<usize, CodecErr> {
+        if buf.len() < FLAKE_SIZE {
+            return Err(CodecErr::BufferTooSmall);
+        }
+        buf[..FLAKE_SIZE].copy_from_slice(&flake.to_bytes(self.endianness));
+        Ok(FLAKE_SIZE)
+    }
+}
+
+/// Reads [Flake] identifiers from byte buffers without allocating.
+pub struct Decoder {
+    endianness: Endianness,
+}
+
+impl Decoder {
+    /// Create a decoder that reads identifiers in the given byte order.
+    pub fn new(endianness: Endianness) -> Decoder {
+        Decoder { endianness }
+    }
+
+    /// Decode a [Flake] from the front of `buf`, returning the identifier together with the number
+    /// of bytes consumed. Fails with [CodecErr::BufferTooSmall] if `buf` is shorter than a full
+    /// identifier.
+    pub fn decode(&self, buf: &[u8]) -> Result<(Flake, usize), CodecErr> {
+        if buf.len() < FLAKE_SIZE {
+            return Err(CodecErr::BufferTooSmall);
+        }
+        let mut bytes = [0u8; FLAKE_SIZE];
+        bytes.copy_from_slice(&buf[..FLAKE_SIZE]);
+        Ok((Flake::from_bytes(bytes, self.endianness), FLAKE_SIZE))
+    }
+}
+
+/// An error that can happen while encoding or decoding a [Flake] to or from a byte buffer.
+#[derive(Debug, PartialEq, Eq)]
+pub enum CodecErr {
+    /// The supplied buffer was smaller than [FLAKE_SIZE] bytes.
+    BufferTooSmall,
+}
+
+impl Display for CodecErr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("buffer too small to hold a flake identifier")
+    }
+}
+
+impl Error for CodecErr {}
+
+#[cfg(test)]
+mod tests {
+    use super::{Decoder, Encoder, Endianness, FLAKE_SIZE};
+    use crate::id::Flake;
+
+    #[test]
+    fn test_round_trip_big_endian() {
+        let id = Flake::new(29866156537351941961353716432896);
+        let mut buf = [0u8; FLAKE_SIZE];
+        let written = Encoder::new(Endianness::Big).encode(&id, &mut buf).unwrap();
+        assert_eq!(FLAKE_SIZE, written);
+        let (decoded, read) = Decoder::new(Endianness::Big).decode(&buf).unwrap();
+        assert_eq!(FLAKE_SIZE, read);
+        assert_eq!(id, decoded);
+    }
+
+    #[test]
+    fn test_endianness_differs() {
+        let id = Flake::new(29866156537351941961353716432896);
+        assert_ne!(id.to_bytes(Endianness::Little), id.to_bytes(Endianness::Big));
+    }
+
+    #[test]
+    fn test_buffer_too_small() {
+        let id = Flake::new(1);
+        let mut buf = [0u8; FLAKE_SIZE - 1];
+        assert!(Encoder::new(Endianness::Big).encode(&id, &mut buf).is_err());
+    }
+}
@@ -0,0 +1,70 @@
+use crate::gen::FlakeGenErr;
+
+/// The total number of bits a flake identifier is made up of.
+pub const TOTAL_BITS: usize = 128;
+
+/// Describes how the 128 bits of a [Flake](crate::id::Flake) are split between the timestamp, node
+/// and sequence fields, together with a custom epoch offset subtracted from the Unix millisecond
+/// timestamp before encoding.
+///
+/// The default layout allocates 64 bits to the timestamp, 48 to the node and 16 to the sequence
+/// counter with the Unix epoch, matching the original hard-coded split. Choosing a later epoch buys
+/// more years of headroom before the timestamp field overflows, and shrinking the node field frees
+/// bits for a wider sequence counter in high-throughput single-node deployments.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FlakeConfig {
+    timestamp_bits: usize,
+    node_bits: usize,
+    seq_bits: usize,
+    epoch: u128,
+}
+
+impl FlakeConfig {
+    /// Create a new layout, allocating `timestamp_bits`, `node_bits` and `seq_bits` to the three
+    /// fields and subtracting `epoch` (in milliseconds since the Unix epoch) from every timestamp.
+    /// The three bit widths must sum to exactly 128, otherwise [FlakeGenErr::InvalidLayout] is
+    /// returned.
+    pub fn new(
+        timestamp_bits: usize,
+        node_bits: usize,
+        seq_bits: usize,
+        epoch: u128,
+    ) -> Result<FlakeConfig, FlakeGenErr> {
+        if timestamp_bits + node_bits + seq_bits != TOTAL_BITS {
+            return Err(FlakeGenErr::InvalidLayout);
+        }
+        Ok(FlakeConfig {
+            timestamp_bits,
+            node_bits,
+            seq_bits,
+            epoch,
+        })
+    }
+
+    pub fn timestamp_bits(&self) -> usize {
+        self.timestamp_bits
+    }
+
+    pub fn node_bits(&self) -> usize {
+        self.node_bits
+    }
+
+    pub fn seq_bits(&self) -> usize {
+        self.seq_bits
+    }
+
+    pub fn epoch(&self) -> u128 {
+        self.epoch
+    }
+}
+
+impl Default for FlakeConfig {
+    fn default() -> Self {
+        FlakeConfig {
+            timestamp_bits: 64,
+            node_bits: 48,
+            seq_bits: 16,
+            epoch: 0,
+        }
+    }
+}
@@ -1,31 +1,95 @@
+use std::path::Path;
 use std::{error::Error, fmt::Display};
 
 use mac_address::{get_mac_address, MacAddress, MacAddressError};
 
-use crate::{id::Flake, seq::SeqGen};
+use crate::{config::FlakeConfig, id::Flake, persist::Persistence, seq::SeqGen};
 
 pub struct FlakeGen {
     node_id: u64,
     seq: SeqGen,
+    persist: Option<Persistence>,
+    config: FlakeConfig,
 }
 
-const NODE_BITS: usize = 48;
-const SEQ_BITS: usize = 16;
-
 impl FlakeGen {
     /// Create a new flake ID generator with the given `node_id` as the unique identifier for this
-    /// generator of Flake IDs.
+    /// generator of Flake IDs. Fails with [FlakeGenErr::NodeIdTooLarge] if `node_id` does not fit in
+    /// the node field, which for the default layout is 48 bits wide.
+    /// ```
+    /// use flakeid::id::Flake;
+    /// use flakeid::gen::FlakeGen;
+    /// let mut gen = FlakeGen::new(0xC0FEE).expect("node id fits");
+    /// let id: Flake = gen.next().expect("No ID was generated");
+    /// ```
+    pub fn new(node_id: u64) -> Result<FlakeGen, FlakeGenErr> {
+        Self::with_config(node_id, FlakeConfig::default())
+    }
+
+    /// Create a new flake ID generator that encodes identifiers according to the given `config`,
+    /// letting the caller pick a custom bit-layout and epoch instead of the default 64/48/16 split.
+    /// Fails with [FlakeGenErr::NodeIdTooLarge] if `node_id` does not fit in `config`'s node field.
     /// ```
     /// use flakeid::id::Flake;
     /// use flakeid::gen::FlakeGen;
-    /// let mut gen = FlakeGen::new(0xC0FEE);
+    /// use flakeid::config::FlakeConfig;
+    /// // A later epoch and a wider sequence counter for a high-throughput single node.
+    /// let config = FlakeConfig::new(74, 24, 30, 1_609_459_200_000).expect("valid layout");
+    /// let mut gen = FlakeGen::with_config(0xC0FEE, config).expect("node id fits");
     /// let id: Flake = gen.next().expect("No ID was generated");
     /// ```
-    pub fn new(node_id: u64) -> FlakeGen {
-        FlakeGen {
+    pub fn with_config(node_id: u64, config: FlakeConfig) -> Result<FlakeGen, FlakeGenErr> {
+        validate_node_id(node_id, &config)?;
+        Ok(FlakeGen {
             node_id,
-            seq: SeqGen::default(),
-        }
+            seq: SeqGen::new(config.seq_bits()),
+            persist: None,
+            config,
+        })
+    }
+
+    /// Create a new flake ID generator that fills the node field with cryptographically-random
+    /// data. This is a useful fallback on hosts with no resolvable MAC address (containers, some
+    /// cloud VMs) where [FlakeGen::with_mac_addr] would fail.
+    pub fn with_random_node() -> Result<FlakeGen, FlakeGenErr> {
+        let config = FlakeConfig::default();
+        let mut bytes = [0u8; 8];
+        getrandom::getrandom(&mut bytes).map_err(FlakeGenErr::Rng)?;
+        let node_id = u64::from_le_bytes(bytes) & node_mask(&config);
+        Self::with_config(node_id, config)
+    }
+
+    /// Create a new flake ID generator from a canonical `aa:bb:cc:dd:ee:ff` EUI-48 MAC address
+    /// string, folding its six octets into the node id. Fails with [FlakeGenErr::InvalidMac] if the
+    /// string is not a valid EUI-48 address.
+    pub fn with_mac_str(mac: &str) -> Result<FlakeGen, FlakeGenErr> {
+        Self::new(parse_eui48(mac)?)
+    }
+
+    /// Create a new flake ID generator that persists the last successfully generated timestamp to
+    /// `path`, making it safe to restart the process without risking duplicate IDs across a
+    /// backwards clock jump. On startup the last timestamp is read back from the file and the
+    /// generator refuses to generate — returning [FlakeErr::TimeDrift] — until `SystemTime::now()`
+    /// has moved strictly past it. A missing file means there is no prior state.
+    /// ```no_run
+    /// use flakeid::id::Flake;
+    /// use flakeid::gen::FlakeGen;
+    /// let mut gen = FlakeGen::with_persistence(0xC0FEE, "flake.state").expect("Creating generator failed");
+    /// let id: Flake = gen.next().expect("No ID was generated");
+    /// ```
+    pub fn with_persistence<P: AsRef<Path>>(
+        node_id: u64,
+        path: P,
+    ) -> Result<FlakeGen, FlakeGenErr> {
+        let config = FlakeConfig::default();
+        validate_node_id(node_id, &config)?;
+        let (persist, last) = Persistence::load(path.as_ref())?;
+        Ok(FlakeGen {
+            node_id,
+            seq: SeqGen::restored(config.seq_bits(), last),
+            persist: Some(persist),
+            config,
+        })
     }
 
     /// Create a new flake ID generator, using the MAC address of the current host as node ID.
@@ -45,31 +109,85 @@ impl FlakeGen {
         let mac_addr: u64 =
             mac_addr.bytes().iter().fold(0u64, |acc, value| (acc << 8) + (*value as u64));
 
-        Ok(Self::new(mac_addr))
+        Self::new(mac_addr)
     }
 
     /// Try to generate a flake ID. The generation may fail if a clock skew occurs or if
     /// the sequence number has been exhausted, but should otherwise generate an ID successfully.
     pub fn try_next(&mut self) -> Result<Flake, FlakeErr> {
-        let (timestamp, seq): (u128, u16) = self.seq.try_next()?;
-        let value: u128 = Self::build(timestamp, self.node_id, seq);
+        let (timestamp, seq): (u128, u64) = self.seq.try_next()?;
+        if let Some(persist) = self.persist.as_mut() {
+            persist.flush(timestamp).map_err(FlakeErr::Persistence)?;
+        }
+        let time: u128 = timestamp.saturating_sub(self.config.epoch());
+        let value: u128 = Self::build(&self.config, time, self.node_id, seq);
         Ok(Flake::new(value))
     }
 
     /// Perform the neccessary bit manipulations to transform
-    /// 0000 0000 aaaa aaaa (timestamp) << 16 * 8
-    /// 0000 0000 00bb bbbb (node) << 2 * 8
+    /// 0000 0000 aaaa aaaa (timestamp) << node + seq bits
+    /// 0000 0000 00bb bbbb (node) << seq bits
     /// 0000 0000 0000 00cc (seq)
     /// into                XOR
     /// aaaa aaaa bbbb bbcc
-    fn build(time: u128, node: u64, seq: u16) -> u128 {
-        let node: u128 = node as u128;
-        let seq: u128 = seq as u128;
-        let time = time << (NODE_BITS + SEQ_BITS);
-        let node = node << SEQ_BITS;
+    /// where the width of each field is taken from `config`. `time` is expected to already have the
+    /// configured epoch subtracted. Each field is masked to its configured width so that an
+    /// oversized value can never bleed into a neighbouring field or overflow the 128-bit identifier.
+    pub(crate) fn build(config: &FlakeConfig, time: u128, node: u64, seq: u64) -> u128 {
+        let time: u128 = (time & field_mask(config.timestamp_bits()))
+            << (config.node_bits() + config.seq_bits());
+        let node: u128 = ((node as u128) & field_mask(config.node_bits())) << config.seq_bits();
+        let seq: u128 = (seq as u128) & field_mask(config.seq_bits());
+
+        time | node | seq
+    }
+}
+
+/// A mask covering the lowest `bits` bits of a 128-bit identifier.
+fn field_mask(bits: usize) -> u128 {
+    if bits >= u128::BITS as usize {
+        u128::MAX
+    } else {
+        (1u128 << bits) - 1
+    }
+}
+
+/// The largest node id that fits in the node field of `config`.
+fn node_mask(config: &FlakeConfig) -> u64 {
+    let bits = config.node_bits();
+    if bits >= u64::BITS as usize {
+        u64::MAX
+    } else {
+        (1u64 << bits) - 1
+    }
+}
+
+/// Ensure `node_id` fits in the node field of `config`, rather than letting [FlakeGen::build]
+/// silently mask away its high bits.
+pub(crate) fn validate_node_id(node_id: u64, config: &FlakeConfig) -> Result<(), FlakeGenErr> {
+    if node_id > node_mask(config) {
+        Err(FlakeGenErr::NodeIdTooLarge)
+    } else {
+        Ok(())
+    }
+}
 
-        node ^ time ^ seq
+/// Parse a canonical `aa:bb:cc:dd:ee:ff` EUI-48 MAC address into the folded `u64` node id.
+fn parse_eui48(mac: &str) -> Result<u64, FlakeGenErr> {
+    let mut node: u64 = 0;
+    let mut octets: usize = 0;
+    for part in mac.split(':') {
+        if part.len() != 2 || !part.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(FlakeGenErr::InvalidMac);
+        }
+        let octet = u8::from_str_radix(part, 16).map_err(|_| FlakeGenErr::InvalidMac)?;
+        node = (node << 8) | (octet as u64);
+        octets += 1;
+    }
+    if octets != 6 {
+        return Err(FlakeGenErr::InvalidMac);
     }
+    Ok(node)
 }
 
 impl Iterator for FlakeGen {
@@ -89,11 +207,36 @@ pub enum FlakeGenErr {
     /// No MAC address could be found on the host device, which makes it impossible to generate
     /// flake ids that are globally unique.
     NoMacAddr(Option<MacAddressError>),
+    /// The persisted generator state could not be read or written.
+    Persistence(std::io::Error),
+    /// The requested bit-layout does not sum to the 128 bits of a flake identifier.
+    InvalidLayout,
+    /// The supplied node id does not fit within the configured node field.
+    NodeIdTooLarge,
+    /// The supplied string is not a valid canonical EUI-48 MAC address.
+    InvalidMac,
+    /// No cryptographically-random data could be read to generate a node id.
+    Rng(getrandom::Error),
 }
 
 impl Display for FlakeGenErr {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_str("unable to acquire MAC address")
+        match self {
+            FlakeGenErr::NoMacAddr(_) => f.write_str("unable to acquire MAC address"),
+            FlakeGenErr::Persistence(_) => f.write_str("unable to access persisted generator state"),
+            FlakeGenErr::InvalidLayout => {
+                f.write_str("flake bit-layout does not sum to 128 bits")
+            }
+            FlakeGenErr::NodeIdTooLarge => f.write_str("node id does not fit in the node field"),
+            FlakeGenErr::InvalidMac => f.write_str("not a valid EUI-48 MAC address"),
+            FlakeGenErr::Rng(_) => f.write_str("unable to acquire random data for node id"),
+        }
+    }
+}
+
+impl From<std::io::Error> for FlakeGenErr {
+    fn from(err: std::io::Error) -> Self {
+        FlakeGenErr::Persistence(err)
     }
 }
 
@@ -102,6 +245,11 @@ impl Error for FlakeGenErr {
         match self {
             FlakeGenErr::NoMacAddr(Some(err)) => Some(err),
             FlakeGenErr::NoMacAddr(None) => None,
+            FlakeGenErr::Persistence(err) => Some(err),
+            FlakeGenErr::InvalidLayout => None,
+            FlakeGenErr::NodeIdTooLarge => None,
+            FlakeGenErr::InvalidMac => None,
+            FlakeGenErr::Rng(err) => Some(err),
         }
     }
 
@@ -124,16 +272,20 @@ pub enum FlakeErr {
     /// last succesfully generated ID is not safe, since it could result in the same ID being
     /// generated twice.
     TimeDrift,
-    /// The sequence number has been temporarily exhausted. This will happen if more IDs than
-    /// what can be held in two bytes (65 536) is generated in a millisecond. When this occurs it is
-    /// always possible to retry generating a flake ID the next millisecond since that will reset
-    /// the sequence counter.
+    /// The sequence number has been temporarily exhausted. This will happen if more IDs than the
+    /// configured sequence field can hold (65 536 for the default 16-bit counter) is generated in a
+    /// millisecond. When this occurs it is always possible to retry generating a flake ID the next
+    /// millisecond since that will reset the sequence counter.
     Exhausted,
+    /// The persisted generator state could not be written back to disk. Since continuing without a
+    /// durable record of the timestamp would defeat the restart safety guarantee, generation fails.
+    Persistence(std::io::Error),
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::gen::FlakeGen;
+    use crate::config::FlakeConfig;
+    use crate::gen::{FlakeGen, FlakeGenErr};
     use crate::id::Flake;
 
     #[quickcheck]
@@ -143,11 +295,14 @@ mod tests {
         node: u64,
         seq: u16,
     ) -> bool {
+        let config = FlakeConfig::default();
+        let ts0 = ts0 & super::field_mask(config.timestamp_bits());
+        let ts1 = ts1 & super::field_mask(config.timestamp_bits());
         if ts0 == ts1 {
             return true;
         }
-        let id0 = FlakeGen::build(ts0, node, seq);
-        let id1 = FlakeGen::build(ts1, node, seq);
+        let id0 = FlakeGen::build(&config, ts0, node, seq as u64);
+        let id1 = FlakeGen::build(&config, ts1, node, seq as u64);
         id0 != id1
     }
 
@@ -158,11 +313,14 @@ mod tests {
         node1: u64,
         seq: u16,
     ) -> bool {
+        let config = FlakeConfig::default();
+        let node0 = node0 & super::node_mask(&config);
+        let node1 = node1 & super::node_mask(&config);
         if node0 == node1 {
             return true;
         }
-        let id0 = FlakeGen::build(ts, node0, seq);
-        let id1 = FlakeGen::build(ts, node1, seq);
+        let id0 = FlakeGen::build(&config, ts, node0, seq as u64);
+        let id1 = FlakeGen::build(&config, ts, node1, seq as u64);
         id0 != id1
     }
 
@@ -176,11 +334,46 @@ mod tests {
         if seq0 == seq1 {
             return true;
         }
-        let id0 = FlakeGen::build(ts, node, seq0);
-        let id1 = FlakeGen::build(ts, node, seq1);
+        let config = FlakeConfig::default();
+        let id0 = FlakeGen::build(&config, ts, node, seq0 as u64);
+        let id1 = FlakeGen::build(&config, ts, node, seq1 as u64);
         id0 != id1
     }
 
+    #[test]
+    fn test_invalid_layout_is_rejected() {
+        assert!(FlakeConfig::new(64, 48, 15, 0).is_err());
+    }
+
+    #[test]
+    fn test_custom_epoch_round_trips_timestamp() {
+        let epoch: u128 = 1_609_459_200_000;
+        let config = FlakeConfig::new(64, 48, 16, epoch).unwrap();
+        let id: Flake = Flake::new(FlakeGen::build(&config, 1_656_452_611_131 - epoch, 0, 0));
+        assert_eq!(1_656_452_611_131, id.timestamp_with(&config));
+    }
+
+    #[test]
+    fn test_node_id_too_large_is_rejected() {
+        assert!(matches!(FlakeGen::new(1u64 << 48), Err(FlakeGenErr::NodeIdTooLarge)));
+    }
+
+    #[test]
+    fn test_with_mac_str_parses_eui48() {
+        assert!(FlakeGen::with_mac_str("aa:bb:cc:dd:ee:ff").is_ok());
+    }
+
+    #[test]
+    fn test_with_mac_str_rejects_invalid() {
+        assert!(matches!(FlakeGen::with_mac_str("zz:zz"), Err(FlakeGenErr::InvalidMac)));
+    }
+
+    #[test]
+    fn test_with_random_node_fits_node_field() {
+        let mut gen = FlakeGen::with_random_node().unwrap();
+        assert!(gen.next().is_some());
+    }
+
     #[test]
     fn two_ids_are_not_same() {
         let mut gen = FlakeGen::with_mac_addr().unwrap();
@@ -1,12 +1,25 @@
 use core::hash::Hash;
+use crate::codec::Endianness;
+use crate::config::FlakeConfig;
 use data_encoding::BASE64;
 use std::convert::TryFrom;
+use std::error::Error;
 use std::fmt::{LowerHex, UpperHex};
+use std::str::FromStr;
 use std::{
     fmt::{Binary, Display},
     u128,
 };
 
+/// The base62 alphabet, ordered so that its characters are ascending in ASCII (`0-9`, then `A-Z`,
+/// then `a-z`). Encoding a flake big-endian with a fixed width over this alphabet preserves the
+/// k-ordering property in plain string sorts, which is valuable for database keys and URL slugs.
+const BASE62_ALPHABET: &[u8; 62] =
+    b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+/// The fixed number of base62 characters needed to represent all 128 bits of a flake.
+const BASE62_LEN: usize = 22;
+
 #[derive(Debug, Eq, Clone, Copy)]
 pub struct Flake(u128);
 
@@ -19,26 +32,91 @@ impl Flake {
         self.0
     }
 
-    /// Byte array representation of the Flake ID. Endianness is always little-endianness so byte
-    /// representation is consistent across different platforms.
+    /// Encode the identifier as a fixed-width, zero-padded base62 string. Because the alphabet is
+    /// ordered by ASCII value and the width is constant, lexicographic ordering of the encoded
+    /// strings matches the numeric (and therefore time) ordering of the identifiers.
+    pub fn to_base62(&self) -> String {
+        let mut value: u128 = self.0;
+        let mut buf: [u8; BASE62_LEN] = [b'0'; BASE62_LEN];
+        let mut i: usize = BASE62_LEN;
+        loop {
+            i -= 1;
+            buf[i] = BASE62_ALPHABET[(value % 62) as usize];
+            value /= 62;
+            if value == 0 {
+                break;
+            }
+        }
+        // The leading, unwritten positions are already padded with '0'.
+        String::from_utf8(buf.to_vec()).expect("base62 alphabet is valid ASCII")
+    }
+
+    /// Decode a base62 string produced by [Flake::to_base62] back into a [Flake]. Fails if the
+    /// string contains a character outside the base62 alphabet or if it would overflow 128 bits.
+    pub fn from_base62(s: &str) -> Result<Flake, ParseFlakeErr> {
+        if s.is_empty() {
+            return Err(ParseFlakeErr::Empty);
+        }
+        let mut value: u128 = 0;
+        for byte in s.bytes() {
+            let digit: u128 = match BASE62_ALPHABET.iter().position(|b| *b == byte) {
+                Some(pos) => pos as u128,
+                None => return Err(ParseFlakeErr::InvalidChar(byte as char)),
+            };
+            value = value
+                .checked_mul(62)
+                .and_then(|v| v.checked_add(digit))
+                .ok_or(ParseFlakeErr::Overflow)?;
+        }
+        Ok(Flake::new(value))
+    }
+
+    /// Byte array representation of the Flake ID. The byte order is big-endian, matching the
+    /// `Display`/serde string representations so that the byte API and the string API never disagree
+    /// on ordering. Use [Flake::to_bytes] to pick the byte order explicitly.
     #[inline(always)]
     pub fn bytes(&self) -> [u8; 16] {
-        self.0.to_le_bytes()
+        self.0.to_be_bytes()
+    }
+
+    /// Byte array representation of the Flake ID in the requested byte order.
+    pub fn to_bytes(&self, endianness: Endianness) -> [u8; 16] {
+        match endianness {
+            Endianness::Little => self.0.to_le_bytes(),
+            Endianness::Big => self.0.to_be_bytes(),
+        }
+    }
+
+    /// Create a Flake ID from 16 bytes interpreted in the requested byte order.
+    pub fn from_bytes(bytes: [u8; 16], endianness: Endianness) -> Flake {
+        let value = match endianness {
+            Endianness::Little => u128::from_le_bytes(bytes),
+            Endianness::Big => u128::from_be_bytes(bytes),
+        };
+        Flake::new(value)
     }
 
     /// Returns a timestamp in form of number of **milliseconds** since UNIX epoch time
-    /// (1st of January 1970 UTC).
+    /// (1st of January 1970 UTC). This assumes the default 64/48/16 bit-layout with the Unix epoch;
+    /// use [Flake::timestamp_with] to decode identifiers generated with a custom [FlakeConfig].
     pub fn timestamp(&self) -> u64 {
-        let ts: u128 = self.0 >> 64;
+        self.timestamp_with(&FlakeConfig::default())
+    }
+
+    /// Returns the timestamp in milliseconds since UNIX epoch, decoding the identifier with the same
+    /// [FlakeConfig] it was generated with so that the bit-shift and epoch offset line up.
+    pub fn timestamp_with(&self, config: &FlakeConfig) -> u64 {
+        let ts: u128 = (self.0 >> (config.node_bits() + config.seq_bits())) + config.epoch();
         u64::try_from(ts).expect("Timestamp must fit into an usigned 64 bit integer")
     }
 }
 
 impl From<[u8; 16]> for Flake {
-    /// Creates a flake id from an array of 16 bytes. Endianness of the byte array is assumed to be
-    /// little endianess.
+    /// Creates a flake id from an array of 16 bytes. The byte order is big-endian, mirroring
+    /// [Flake::bytes] and the `Display`/serde representations. Use [Flake::from_bytes] to decode a
+    /// specific byte order explicitly.
     fn from(value: [u8; 16]) -> Self {
-        Flake::new(u128::from_le_bytes(value))
+        Flake::new(u128::from_be_bytes(value))
     }
 }
 
@@ -90,6 +168,49 @@ impl Display for Flake {
     }
 }
 
+impl FromStr for Flake {
+    type Err = ParseFlakeErr;
+
+    /// Parse a [Flake] from its fixed-width base62 form, or from a bare decimal `u128` string (as
+    /// Pleroma accepts bare integer strings for legacy IDs). A value is treated as base62 only when
+    /// it has the fixed base62 width *and* contains at least one non-digit letter, so an all-digit
+    /// string is always parsed as a decimal integer and legacy decimal IDs round-trip correctly.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let looks_like_base62 = s.len() == BASE62_LEN && s.bytes().any(|b| !b.is_ascii_digit());
+        if looks_like_base62 {
+            Flake::from_base62(s)
+        } else {
+            s.parse::<u128>().map(Flake::new).map_err(|_| ParseFlakeErr::InvalidDecimal)
+        }
+    }
+}
+
+/// An error that can happen when parsing a [Flake] from a string.
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseFlakeErr {
+    /// The input string was empty.
+    Empty,
+    /// The input contained a character that is not part of the base62 alphabet.
+    InvalidChar(char),
+    /// The decoded value did not fit within the 128 bits of a flake identifier.
+    Overflow,
+    /// The input was not a valid decimal `u128` integer.
+    InvalidDecimal,
+}
+
+impl Display for ParseFlakeErr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseFlakeErr::Empty => f.write_str("empty flake string"),
+            ParseFlakeErr::InvalidChar(c) => write!(f, "invalid base62 character '{c}'"),
+            ParseFlakeErr::Overflow => f.write_str("flake value overflows 128 bits"),
+            ParseFlakeErr::InvalidDecimal => f.write_str("not a valid decimal flake value"),
+        }
+    }
+}
+
+impl Error for ParseFlakeErr {}
+
 #[cfg(test)]
 mod tests {
     use super::Flake;
@@ -102,6 +223,62 @@ mod tests {
         assert_eq!(id0, id1);
     }
 
+    #[test]
+    fn test_bytes_are_big_endian() {
+        use crate::codec::Endianness;
+        let id = Flake::new(29866156537351941961353716432896);
+        assert_eq!(id.bytes(), id.to_bytes(Endianness::Big));
+    }
+
+    #[test]
+    fn test_base62_round_trip() {
+        let id0 = Flake::new(29866156537351941961353716432896);
+        let encoded = id0.to_base62();
+        let id1 = Flake::from_base62(&encoded).unwrap();
+        assert_eq!(id0, id1);
+    }
+
+    #[test]
+    fn test_base62_is_fixed_width_and_sortable() {
+        let small = Flake::new(1).to_base62();
+        let large = Flake::new(u128::MAX).to_base62();
+        assert_eq!(22, small.len());
+        assert_eq!(22, large.len());
+        assert!(small < large);
+    }
+
+    #[test]
+    fn test_from_str_accepts_base62_and_decimal() {
+        let id = Flake::new(29866156537351941961353716432896);
+        assert_eq!(id, id.to_base62().parse().unwrap());
+        assert_eq!(Flake::new(123), "123".parse().unwrap());
+    }
+
+    #[test]
+    fn test_all_digit_base62_is_parsed_as_decimal() {
+        // A flake whose base62 encoding happens to be all digits is ambiguous with a legacy decimal
+        // id; `FromStr` intentionally resolves it as decimal, so such values do not round-trip.
+        // `from_base62` is unambiguous and should still be used when the base62 form is known.
+        let id: Flake = Flake::new(62);
+        let encoded = id.to_base62();
+        assert!(encoded.bytes().all(|b| b.is_ascii_digit()));
+        assert_eq!(Flake::new(10), encoded.parse().unwrap());
+        assert_eq!(id, Flake::from_base62(&encoded).unwrap());
+    }
+
+    #[test]
+    fn test_from_str_parses_22_digit_decimal_as_decimal() {
+        // A legacy decimal id with exactly 22 digits must not be mistaken for base62.
+        let decimal = "1234567890123456789012";
+        let expected: u128 = decimal.parse().unwrap();
+        assert_eq!(Flake::new(expected), decimal.parse().unwrap());
+    }
+
+    #[test]
+    fn test_from_base62_rejects_invalid_char() {
+        assert_eq!(Some(super::ParseFlakeErr::InvalidChar('-')), Flake::from_base62("-").err());
+    }
+
     #[test]
     fn test_timestamp() {
         let id = Flake::new(30556157387769903979283677052928);
@@ -7,12 +7,22 @@ extern crate serde_test;
 #[cfg(test)]
 #[macro_use(quickcheck)]
 extern crate quickcheck_macros;
+/// Module providing zero-copy encoding/decoding and explicit byte-order control for flake identifiers
+pub mod codec;
+
+/// Module describing the configurable bit-layout and epoch of flake identifiers
+pub mod config;
+
 /// Module which contains logic for generation of flake identifiers
 pub mod gen;
 
 /// Module for the [id::Flake] struct, i.e. the representation of the flake identifier
 pub mod id;
+mod persist;
 mod seq;
 
+/// Module providing a thread-safe, shareable flake identifier generator
+pub mod sync;
+
 #[cfg(feature = "serde")]
 pub mod serde;
@@ -0,0 +1,65 @@
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+/// Persists the last successfully generated timestamp to disk so that a `FlakeGen` can be safely
+/// restarted without risking k-ordering violations or duplicate IDs after a backwards clock jump
+/// (VM snapshot restore, NTP step, ...). The on-disk format is simply the 8-byte little-endian
+/// millisecond value; a missing file means "no prior state".
+pub(crate) struct Persistence {
+    path: PathBuf,
+    /// The last timestamp we have written to disk. Used to throttle writes so that we do not flush
+    /// once per generated ID when many IDs are produced within the same millisecond.
+    flushed: u128,
+}
+
+impl Persistence {
+    /// Load the persisted state from `path`. Returns the [Persistence] handle together with the
+    /// last timestamp that was recorded, or `0` if the file does not yet exist.
+    pub fn load(path: &Path) -> Result<(Persistence, u128), std::io::Error> {
+        let last: u128 = match File::open(path) {
+            Ok(mut file) => {
+                let mut buf = Vec::with_capacity(8);
+                file.read_to_end(&mut buf)?;
+                // A short or empty file — e.g. one left behind by a crash mid-write — is treated as
+                // "no prior state" rather than bricking startup with an `UnexpectedEof`.
+                match buf.get(..8) {
+                    Some(value) => {
+                        u64::from_le_bytes(value.try_into().expect("slice is 8 bytes")) as u128
+                    }
+                    None => 0,
+                }
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => 0,
+            Err(err) => return Err(err),
+        };
+        let persist = Persistence {
+            path: path.to_path_buf(),
+            flushed: last,
+        };
+        Ok((persist, last))
+    }
+
+    /// Record `timestamp` to disk. Writes are throttled to at most once per millisecond, so calling
+    /// this for every generated ID only results in a single write — and a single `fsync` — per
+    /// millisecond. The `sync_all` is what makes the floor actually durable across a power loss,
+    /// NTP step or snapshot restore; without it the last timestamp could be lost from the page
+    /// cache and re-open the duplicate-issuance window the persistence is meant to close.
+    pub fn flush(&mut self, timestamp: u128) -> Result<(), std::io::Error> {
+        if timestamp <= self.flushed {
+            return Ok(());
+        }
+        let value: u64 = timestamp as u64;
+        // Write to a sibling temp file and atomically `rename` it over the target, so the state
+        // file is never observed truncated: a crash between the write and the rename leaves the
+        // previous, still-durable timestamp in place rather than a short file that would silently
+        // disable the restart guard.
+        let tmp: PathBuf = self.path.with_extension("tmp");
+        let mut file = File::create(&tmp)?;
+        file.write_all(&value.to_le_bytes())?;
+        file.sync_all()?;
+        std::fs::rename(&tmp, &self.path)?;
+        self.flushed = timestamp;
+        Ok(())
+    }
+}
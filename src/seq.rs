@@ -4,27 +4,58 @@ use crate::gen::FlakeErr;
 
 pub(crate) struct SeqGen {
     timestamp: u128,
-    seq: u16,
+    seq: u64,
+    /// The largest sequence number this generator will hand out within a single millisecond,
+    /// derived from the configured number of sequence bits. The counter wraps to
+    /// [FlakeErr::Exhausted] once it is passed.
+    max_seq: u64,
+    /// An exclusive lower bound on the timestamp we are allowed to generate with. When restoring a
+    /// generator from persisted state this is set to the last timestamp seen before the restart, so
+    /// that generation refuses until the clock has strictly moved past it. Zero disables the guard.
+    floor: u128,
 }
 
-impl Default for SeqGen {
-    fn default() -> Self {
+impl SeqGen {
+    /// Create a sequence generator whose counter is `seq_bits` wide, so that shrinking the node
+    /// field really does widen the per-millisecond sequence space.
+    pub fn new(seq_bits: usize) -> SeqGen {
         SeqGen {
             timestamp: 0,
             seq: 0,
+            max_seq: Self::max_seq(seq_bits),
+            floor: 0,
         }
     }
-}
 
-impl SeqGen {
-    pub fn try_next(&mut self) -> Result<(u128, u16), FlakeErr> {
+    /// Create a sequence generator with a `seq_bits`-wide counter that refuses to generate until
+    /// `SystemTime::now()` has moved strictly past `floor` milliseconds, used when restoring
+    /// persisted state across a restart.
+    pub fn restored(seq_bits: usize, floor: u128) -> SeqGen {
+        SeqGen {
+            timestamp: 0,
+            seq: 0,
+            max_seq: Self::max_seq(seq_bits),
+            floor,
+        }
+    }
+
+    fn max_seq(seq_bits: usize) -> u64 {
+        if seq_bits >= u64::BITS as usize {
+            u64::MAX
+        } else {
+            (1u64 << seq_bits) - 1
+        }
+    }
+
+    pub fn try_next(&mut self) -> Result<(u128, u64), FlakeErr> {
         let now: u128 = Self::time()?;
-        let seq: u16 = match self.timestamp.cmp(&now) {
+        if now <= self.floor {
+            return Err(FlakeErr::TimeDrift);
+        }
+        let seq: u64 = match self.timestamp.cmp(&now) {
             std::cmp::Ordering::Less => 0,
-            std::cmp::Ordering::Equal => match self.seq.checked_add(1) {
-                Some(n) => n,
-                None => return Err(FlakeErr::Exhausted),
-            },
+            std::cmp::Ordering::Equal if self.seq < self.max_seq => self.seq + 1,
+            std::cmp::Ordering::Equal => return Err(FlakeErr::Exhausted),
             std::cmp::Ordering::Greater => return Err(FlakeErr::TimeDrift),
         };
         self.timestamp = now;
@@ -33,11 +64,17 @@ impl SeqGen {
     }
 
     fn time() -> Result<u128, FlakeErr> {
-        let now = SystemTime::now();
-        let elapsed: Duration = match now.duration_since(SystemTime::UNIX_EPOCH) {
-            Ok(elapsed) => elapsed,
-            Err(_) => return Err(FlakeErr::TimeDrift),
-        };
-        Ok(elapsed.as_millis())
+        now_millis()
     }
 }
+
+/// The current number of milliseconds since the UNIX epoch, or [FlakeErr::TimeDrift] if the system
+/// clock is set before the epoch.
+pub(crate) fn now_millis() -> Result<u128, FlakeErr> {
+    let now = SystemTime::now();
+    let elapsed: Duration = match now.duration_since(SystemTime::UNIX_EPOCH) {
+        Ok(elapsed) => elapsed,
+        Err(_) => return Err(FlakeErr::TimeDrift),
+    };
+    Ok(elapsed.as_millis())
+}
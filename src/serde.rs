@@ -38,7 +38,9 @@ impl<'de> Visitor<'de> for FlakeVisitor {
     where
         E: Error,
     {
-        let decoded_bytes = data_encoding::BASE64.decode(v.as_bytes()).unwrap();
+        let decoded_bytes = data_encoding::BASE64
+            .decode(v.as_bytes())
+            .map_err(|err| E::custom(format!("invalid base64 Flake ID: {err}")))?;
         let mut bytes = [0u8; 16];
         for (i, byte) in decoded_bytes.iter().enumerate() {
             bytes[i] = *byte;